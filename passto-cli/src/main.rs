@@ -4,7 +4,7 @@ use std::process;
 use std::time::Instant;
 use clap::Parser;
 use log::{error, info, LevelFilter};
-use passto::{AlgorithmSettings, DigestAlgorithm, encode, HashingAlgorithm, SaltingAlgorithm};
+use passto::{AlgorithmSettings, CharacterClassPolicy, derive_keypair, DigestAlgorithm, encode, fingerprint, HashingAlgorithm, SaltingAlgorithm};
 use rand::RngCore;
 
 #[derive(Parser, Debug)]
@@ -16,6 +16,21 @@ struct Args {
     /// Use sha512 for hashing
     #[arg(long, default_value_t = false)]
     sha512: bool,
+    /// Use Argon2id for hashing (memory-hard, brute-force resistant)
+    #[arg(long, default_value_t = false)]
+    argon2: bool,
+    /// Argon2id memory cost in KiB
+    #[arg(long, default_value_t = 19456)]
+    argon2_mem_kib: u32,
+    /// Argon2id time cost (number of passes)
+    #[arg(long, default_value_t = 2)]
+    argon2_time_cost: u32,
+    /// Argon2id parallelism (lanes)
+    #[arg(long, default_value_t = 1)]
+    argon2_parallelism: u32,
+    /// Argon2id raw output length in bytes
+    #[arg(long, default_value_t = 32)]
+    argon2_hash_len: usize,
     /// Use ZIP salting
     #[arg(long)]
     zip: Option<usize>,
@@ -43,9 +58,27 @@ struct Args {
     /// Use custom alphabet
     #[arg(long)]
     alphabet: Option<String>,
+    /// Render the password as a diceware-style sequence of this many words
+    #[arg(long)]
+    wordlist: Option<usize>,
+    /// Separator between words in wordlist mode
+    #[arg(long, default_value = "-")]
+    word_separator: String,
     /// Max length for your password
     #[arg(long)]
     max_length: Option<usize>,
+    /// Minimum number of uppercase letters required in the output
+    #[arg(long, default_value_t = 0)]
+    min_upper: usize,
+    /// Minimum number of lowercase letters required in the output
+    #[arg(long, default_value_t = 0)]
+    min_lower: usize,
+    /// Minimum number of digits required in the output
+    #[arg(long, default_value_t = 0)]
+    min_digit: usize,
+    /// Minimum number of special characters required in the output
+    #[arg(long, default_value_t = 0)]
+    min_special: usize,
     /// Salt used for your password. Random by default
     #[arg(long)]
     salt: Option<String>,
@@ -54,6 +87,15 @@ struct Args {
     /// Output generation time
     #[arg(long, default_value_t = false)]
     time: bool,
+    /// Derive an Ed25519 key pair instead of a password and print the public key
+    #[arg(long, default_value_t = false)]
+    keypair: bool,
+    /// Also print the derived secret key (sensitive, brain-wallet style)
+    #[arg(long, default_value_t = false)]
+    show_secret: bool,
+    /// Print a fingerprint of the passphrase, to catch typos, and exit
+    #[arg(long, default_value_t = false)]
+    fingerprint: bool,
 }
 
 fn build_settings(args: &Args) -> AlgorithmSettings {
@@ -72,7 +114,14 @@ fn build_settings(args: &Args) -> AlgorithmSettings {
         settings.salting = SaltingAlgorithm::Prepend;
     }
 
-    if args.sha512 {
+    if args.argon2 {
+        settings.hashing = HashingAlgorithm::Argon2id {
+            mem_kib: args.argon2_mem_kib,
+            time_cost: args.argon2_time_cost,
+            parallelism: args.argon2_parallelism,
+            hash_len: args.argon2_hash_len,
+        };
+    } else if args.sha512 {
         settings.hashing = HashingAlgorithm::Sha512;
     } else {
         settings.hashing = HashingAlgorithm::Sha256;
@@ -84,10 +133,24 @@ fn build_settings(args: &Args) -> AlgorithmSettings {
         settings.digest = DigestAlgorithm::Base64Url;
     } else if let Some(alphabet) = &args.alphabet {
         settings.digest = DigestAlgorithm::CustomAlphabet(alphabet.clone());
+    } else if let Some(word_count) = args.wordlist {
+        settings.digest = DigestAlgorithm::Wordlist {
+            word_count,
+            separator: args.word_separator.clone(),
+        };
     } else if args.base64 {
         settings.digest = DigestAlgorithm::Base64;
     }
 
+    if args.min_upper > 0 || args.min_lower > 0 || args.min_digit > 0 || args.min_special > 0 {
+        settings.policy = Some(CharacterClassPolicy {
+            min_uppercase: args.min_upper,
+            min_lowercase: args.min_lower,
+            min_digit: args.min_digit,
+            min_special: args.min_special,
+        });
+    }
+
     settings
 }
 
@@ -124,6 +187,21 @@ fn handle_res<T: Display>(r: passto::Result<T>) {
     }
 }
 
+fn handle_keypair(r: passto::Result<passto::KeyPair>, show_secret: bool) {
+    match r {
+        Ok(keypair) => {
+            println!("{keypair}");
+            if show_secret {
+                println!("{}", keypair.secret_key_hex());
+            }
+        },
+        Err(e) => {
+            error!("{e}");
+            process::exit(1);
+        },
+    }
+}
+
 fn main() {
     env_logger::builder()
         .filter_level(LevelFilter::Debug)
@@ -133,13 +211,24 @@ fn main() {
 
     let args = Args::parse();
 
-    let settings = build_settings(&args);
     let salt = get_salt(&args);
+
+    if args.fingerprint {
+        println!("{}", fingerprint(&salt));
+        return;
+    }
+
+    let settings = build_settings(&args);
     let service = get_service(&args);
 
     let begin = Instant::now();
-    handle_res(encode(&salt, &service, &settings));
-    
+
+    if args.keypair {
+        handle_keypair(derive_keypair(&salt, &service, &settings), args.show_secret);
+    } else {
+        handle_res(encode(&salt, &service, &settings));
+    }
+
     if args.time {
         info!("Finished in {:?}", Instant::now() - begin);
     }
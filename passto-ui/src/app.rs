@@ -2,7 +2,46 @@ use std::time;
 use eframe::{App, Frame};
 use egui::{Color32, ComboBox, Context, Grid, RichText, TextEdit, Ui};
 use log::error;
-use passto::{AlgorithmSettings, DigestAlgorithm, encode, HashingAlgorithm, SaltingAlgorithm};
+use passto::{AlgorithmSettings, CharacterClassPolicy, derive_keypair, DigestAlgorithm, encode, fingerprint, HashingAlgorithm, SaltingAlgorithm};
+
+/// Caches a derivation so Argon2id doesn't re-run on every repaint.
+#[derive(Debug, Clone)]
+struct OutputCache {
+    salt: String,
+    password: String,
+    settings: AlgorithmSettings,
+    duration: time::Duration,
+    result: core::result::Result<String, String>,
+}
+
+fn compute_cached(
+    cache: &mut Option<OutputCache>,
+    salt: &str,
+    password: &str,
+    settings: &AlgorithmSettings,
+    compute: impl FnOnce() -> core::result::Result<String, String>,
+) -> OutputCache {
+    let stale = match cache {
+        Some(c) => c.salt != salt || c.password != password || &c.settings != settings,
+        None => true,
+    };
+
+    if stale {
+        let begin = time::Instant::now();
+        let result = compute();
+        let duration = time::Instant::now() - begin;
+
+        *cache = Some(OutputCache {
+            salt: salt.to_owned(),
+            password: password.to_owned(),
+            settings: settings.clone(),
+            duration,
+            result,
+        });
+    }
+
+    cache.clone().unwrap()
+}
 
 #[derive(Debug, Default, Clone)]
 pub struct PasstoApp {
@@ -16,6 +55,15 @@ pub struct PasstoApp {
     pub custom_alphabet: String,
     pub hashing_iterations: String,
     pub salting_iterations: String,
+    pub min_upper_raw: String,
+    pub min_lower_raw: String,
+    pub min_digit_raw: String,
+    pub min_special_raw: String,
+    pub wordlist_count_raw: String,
+    pub wordlist_separator: String,
+    pub show_keypair: bool,
+    password_cache: Option<OutputCache>,
+    keypair_cache: Option<OutputCache>,
 }
 
 impl PasstoApp {
@@ -24,6 +72,7 @@ impl PasstoApp {
             return
         }
         self.zip_raw = "1".into();
+        self.wordlist_separator = "-".into();
 
         if let Some(storage) = frame.storage_mut() {
             self.salt = storage.get_string("passphrase").unwrap_or_default();
@@ -48,10 +97,37 @@ impl PasstoApp {
         self.service_row(ui);
         self.digest_row(ui);
         self.alphabet_row(ui);
+        self.wordlist_row(ui);
         self.hashing_row(ui);
         self.salting_row(ui);
         self.zip_row(ui);
         self.length_row(ui);
+        self.policy_row(ui);
+        self.keypair_row(ui);
+    }
+
+    fn keypair_row(&mut self, ui: &mut Ui) {
+        ui.label("Derive key pair");
+        ui.checkbox(&mut self.show_keypair, "");
+        ui.end_row();
+    }
+
+    fn policy_row(&mut self, ui: &mut Ui) {
+        ui.label("Min uppercase");
+        ui.text_edit_singleline(&mut self.min_upper_raw);
+        ui.end_row();
+
+        ui.label("Min lowercase");
+        ui.text_edit_singleline(&mut self.min_lower_raw);
+        ui.end_row();
+
+        ui.label("Min digits");
+        ui.text_edit_singleline(&mut self.min_digit_raw);
+        ui.end_row();
+
+        ui.label("Min special");
+        ui.text_edit_singleline(&mut self.min_special_raw);
+        ui.end_row();
     }
 
     fn alphabet_row(&mut self, ui: &mut Ui) {
@@ -61,6 +137,18 @@ impl PasstoApp {
             ui.end_row();
         }
     }
+
+    fn wordlist_row(&mut self, ui: &mut Ui) {
+        if let DigestAlgorithm::Wordlist { .. } = self.settings.digest {
+            ui.label("Word count");
+            ui.text_edit_singleline(&mut self.wordlist_count_raw);
+            ui.end_row();
+
+            ui.label("Word separator");
+            ui.text_edit_singleline(&mut self.wordlist_separator);
+            ui.end_row();
+        }
+    }
     
     fn length_row(&mut self, ui: &mut Ui) {
         ui.label("Max length");
@@ -115,6 +203,11 @@ impl PasstoApp {
 
                 ui.selectable_value(&mut self.settings.hashing, HashingAlgorithm::Sha256, "SHA256");
                 ui.selectable_value(&mut self.settings.hashing, HashingAlgorithm::Sha512, "SHA512");
+                ui.selectable_value(
+                    &mut self.settings.hashing,
+                    HashingAlgorithm::Argon2id { mem_kib: 19456, time_cost: 2, parallelism: 1, hash_len: 32 },
+                    "Argon2id"
+                );
             });
         ui.end_row();
         
@@ -135,9 +228,17 @@ impl PasstoApp {
                 ui.selectable_value(&mut self.settings.digest, DigestAlgorithm::Base64Url, "Base64Url");
                 ui.selectable_value(
                     &mut self.settings.digest,
-                    DigestAlgorithm::CustomAlphabet(self.custom_alphabet.clone()), 
+                    DigestAlgorithm::CustomAlphabet(self.custom_alphabet.clone()),
                     "Custom alphabet"
                 );
+                ui.selectable_value(
+                    &mut self.settings.digest,
+                    DigestAlgorithm::Wordlist {
+                        word_count: self.wordlist_count_raw.parse().unwrap_or(4),
+                        separator: self.wordlist_separator.clone(),
+                    },
+                    "Wordlist"
+                );
             });
         ui.end_row();
     }
@@ -161,6 +262,10 @@ impl PasstoApp {
                 TextEdit::singleline(&mut self.salt)
                     .password(true)
             );
+
+            if !self.salt.is_empty() {
+                ui.label(fingerprint(self.salt.as_bytes()));
+            }
         });
         ui.end_row();
     }
@@ -175,61 +280,120 @@ impl PasstoApp {
 
         self.grid_wrapper(ui, frame);
         self.handle_variants();
-        self.output_password(ui, frame);
-    }
-    
-    fn resolve_error(&mut self, res: &passto::Result<String>) -> Option<String> {
-        if self.salt.is_empty() || self.password.is_empty() {
-            Some("Please enter a passphrase and service first".into())
-        } else if let Err(e) = res {
-            return Some(format!("{e}"))
+
+        if self.show_keypair {
+            self.output_keypair(ui, frame);
         } else {
-            return None
+            self.output_password(ui, frame);
         }
     }
-
+    
     fn output_password(&mut self, ui: &mut Ui, frame: &mut Frame) {
-        self.settings.hashing_iterations = self.hashing_iterations.parse().unwrap_or(1);
-        self.settings.salting_iterations = self.salting_iterations.parse().unwrap_or(1);
-        
-        let begin = time::Instant::now();
-        let password = encode(self.salt.as_bytes(), self.password.as_bytes(), &self.settings);
-        let end = time::Instant::now();
-        
-        let duration = end - begin;
-        
-        let possible_error = self.resolve_error(&password);
-        
-        if let Some(err) = possible_error {
+        if self.salt.is_empty() || self.password.is_empty() {
             ui.horizontal(|ui| {
                 ui.label(
-                    RichText::new(err)
+                    RichText::new("Please enter a passphrase and service first")
                         .color(Color32::from_rgb(255, 125, 125))
                 );
             });
-        } else {
+
+            ui.end_row();
+            return;
+        }
+
+        let salt = self.salt.clone();
+        let password = self.password.clone();
+        let settings = self.settings.clone();
+
+        let cached = compute_cached(&mut self.password_cache, &salt, &password, &settings, || {
+            encode(salt.as_bytes(), password.as_bytes(), &settings).map_err(|e| e.to_string())
+        });
+
+        match cached.result {
+            Ok(password) => {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Output({:?}): ", cached.duration));
+
+                    if !frame.is_web() && ui.button("Copy").clicked() {
+                        ui.output_mut(|ui| {
+                            ui.copied_text = password.clone();
+                        });
+                    }
+
+                    if password.len() > 64 {
+                        ui.label(format!("{}...", &password[..64]));
+                    } else {
+                        ui.label(password);
+                    }
+                });
+            },
+            Err(e) => {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(e)
+                            .color(Color32::from_rgb(255, 125, 125))
+                    );
+                });
+            },
+        }
+
+        ui.end_row();
+    }
+
+    fn output_keypair(&mut self, ui: &mut Ui, frame: &mut Frame) {
+        if self.salt.is_empty() || self.password.is_empty() {
             ui.horizontal(|ui| {
-                let password = password.unwrap();
-                ui.label(format!("Output({duration:?}): "));
-                
-                if !frame.is_web() && ui.button("Copy").clicked() {
-                    ui.output_mut(|ui| {
-                        ui.copied_text = password.clone();
-                    });
-                }
-                
-                if password.len() > 64 {
-                    ui.label(format!("{}...", &password[..64]));                    
-                } else {
-                    ui.label(password);
-                }
+                ui.label(
+                    RichText::new("Please enter a passphrase and service first")
+                        .color(Color32::from_rgb(255, 125, 125))
+                );
             });
+
+            ui.end_row();
+            return;
         }
-        
+
+        let salt = self.salt.clone();
+        let password = self.password.clone();
+        let settings = self.settings.clone();
+
+        let cached = compute_cached(&mut self.keypair_cache, &salt, &password, &settings, || {
+            derive_keypair(salt.as_bytes(), password.as_bytes(), &settings)
+                .map(|keypair| keypair.public_key_openssh())
+                .map_err(|e| e.to_string())
+        });
+
+        match cached.result {
+            Ok(public) => {
+                ui.horizontal(|ui| {
+                    ui.label("Public key: ");
+
+                    if !frame.is_web() && ui.button("Copy").clicked() {
+                        ui.output_mut(|ui| {
+                            ui.copied_text = public.clone();
+                        });
+                    }
+
+                    ui.label(public);
+                });
+            },
+            Err(e) => {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(e)
+                            .color(Color32::from_rgb(255, 125, 125))
+                    );
+                });
+            },
+        }
+
         ui.end_row();
     }
 
     fn handle_variants(&mut self) {
+        self.settings.hashing_iterations = self.hashing_iterations.parse().unwrap_or(1);
+        self.settings.salting_iterations = self.salting_iterations.parse().unwrap_or(1);
+
         if let SaltingAlgorithm::Zip(_) = self.settings.salting {
             self.settings.salting = SaltingAlgorithm::Zip(
                 self.zip_raw.parse().unwrap_or(1),
@@ -241,8 +405,28 @@ impl PasstoApp {
                 self.custom_alphabet.clone(),
             );
         }
-        
+
+        if let DigestAlgorithm::Wordlist { .. } = self.settings.digest {
+            self.settings.digest = DigestAlgorithm::Wordlist {
+                word_count: self.wordlist_count_raw.parse().unwrap_or(4),
+                separator: self.wordlist_separator.clone(),
+            };
+        }
+
         self.settings.max_length = self.max_length_raw.parse::<usize>().ok();
+
+        let policy = CharacterClassPolicy {
+            min_uppercase: self.min_upper_raw.parse().unwrap_or(0),
+            min_lowercase: self.min_lower_raw.parse().unwrap_or(0),
+            min_digit: self.min_digit_raw.parse().unwrap_or(0),
+            min_special: self.min_special_raw.parse().unwrap_or(0),
+        };
+
+        self.settings.policy = if policy == CharacterClassPolicy::default() {
+            None
+        } else {
+            Some(policy)
+        };
     }
 
     fn grid_wrapper(&mut self, ui: &mut Ui, frame: &mut Frame) {
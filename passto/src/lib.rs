@@ -4,6 +4,21 @@ use base64::{engine::general_purpose, Engine};
 use num_bigint::BigUint;
 use num_traits::Zero;
 use serde::{Deserialize, Serialize};
+use argon2::{Algorithm, Argon2, Params, Version};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use std::sync::OnceLock;
+
+/// Upper bound on the Argon2id memory cost, in KiB, to avoid OOM.
+const MAX_ARGON2_MEM_KIB: u32 = 4 * 1024 * 1024;
+
+/// EFF-style diceware word list, sorted and fixed so `Wordlist` digests stay
+/// stable across versions.
+const WORDLIST_RAW: &str = include_str!("wordlist.txt");
+
+fn wordlist() -> &'static [&'static str] {
+    static WORDLIST: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDLIST.get_or_init(|| WORDLIST_RAW.lines().collect())
+}
 
 #[derive(Debug, thiserror::Error)]
 pub enum HashingError {
@@ -11,6 +26,24 @@ pub enum HashingError {
     CustomAlphabetTooShort,
     #[error("deserialization error")]
     Deserialization(serde_json::Error),
+    #[error("argon2 memory cost too large: {0} KiB")]
+    Argon2MemoryTooLarge(u32),
+    #[error("invalid argon2 parameters: {0}")]
+    InvalidArgon2Parameters(argon2::Error),
+    #[error("argon2 hashing failed: {0}")]
+    Argon2Hashing(argon2::Error),
+    #[error("character class policy requires at least {0} characters but the output is only {1} characters long")]
+    PolicyExceedsMaxLength(usize, usize),
+    #[error("requested {0} words but the hash does not contain enough entropy to produce them")]
+    InsufficientWordlistEntropy(usize),
+    #[error("character class policy requires {0} characters but the {1:?} digest cannot produce any")]
+    PolicyUnsatisfiableForDigest(&'static str, DigestAlgorithm),
+    #[error("character class policy cannot be combined with the Wordlist digest")]
+    PolicyIncompatibleWithWordlist,
+    #[error("max_length cannot be combined with the Wordlist digest")]
+    MaxLengthIncompatibleWithWordlist,
+    #[error("hash() does not support Argon2id; use argon2id() or encode() instead")]
+    Argon2NotSupportedByHash,
 }
 
 pub type Result<T> = core::result::Result<T, HashingError>;
@@ -18,7 +51,16 @@ pub type Result<T> = core::result::Result<T, HashingError>;
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HashingAlgorithm {
     Sha256,
-    Sha512
+    Sha512,
+    /// Memory-hard KDF. `mem_kib` is the memory cost in KiB, `time_cost` the
+    /// number of passes, `parallelism` the lane count, and `hash_len` the
+    /// size in bytes of the raw output fed into `digest()`.
+    Argon2id {
+        mem_kib: u32,
+        time_cost: u32,
+        parallelism: u32,
+        hash_len: usize,
+    },
 }
 
 impl Default for HashingAlgorithm {
@@ -33,6 +75,12 @@ pub enum DigestAlgorithm {
     Base64,
     Base64Url,
     CustomAlphabet(String),
+    /// Renders the hash as `word_count` dictionary words joined by
+    /// `separator`, diceware-style, instead of an opaque character string.
+    Wordlist {
+        word_count: usize,
+        separator: String,
+    },
 }
 
 impl Default for DigestAlgorithm {
@@ -54,7 +102,23 @@ impl Default for SaltingAlgorithm {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Minimum counts per character class that a generated password must satisfy.
+/// See `enforce_policy()`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CharacterClassPolicy {
+    pub min_uppercase: usize,
+    pub min_lowercase: usize,
+    pub min_digit: usize,
+    pub min_special: usize,
+}
+
+impl CharacterClassPolicy {
+    fn total(&self) -> usize {
+        self.min_uppercase + self.min_lowercase + self.min_digit + self.min_special
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct AlgorithmSettings {
     pub hashing: HashingAlgorithm,
     pub max_length: Option<usize>,
@@ -62,6 +126,7 @@ pub struct AlgorithmSettings {
     pub salting: SaltingAlgorithm,
     pub hashing_iterations: usize,
     pub salting_iterations: usize,
+    pub policy: Option<CharacterClassPolicy>,
 }
 
 impl Display for AlgorithmSettings {
@@ -80,6 +145,7 @@ impl Default for AlgorithmSettings {
             salting: Default::default(),
             hashing_iterations: 1,
             salting_iterations: 1,
+            policy: None,
         }
     }
 }
@@ -129,24 +195,84 @@ pub fn digest(digest_algorithm: &DigestAlgorithm, data: &[u8]) -> Result<String>
                 Ok(result)
             }
         },
+        DigestAlgorithm::Wordlist { word_count, separator } => {
+            let words = wordlist();
+            let bits_per_word = (words.len() as f64).log2();
+            let available_bits = (data.len() * 8) as f64;
+
+            if (*word_count as f64) * bits_per_word > available_bits {
+                return Err(HashingError::InsufficientWordlistEntropy(*word_count));
+            }
+
+            let wordlist_length: BigUint = words.len().into();
+            let mut bigint = BigUint::from_bytes_le(data);
+
+            let mut picked = Vec::with_capacity(*word_count);
+
+            for _ in 0..*word_count {
+                let word_idx = (&bigint % &wordlist_length)
+                    .to_u64_digits().first()
+                    .copied()
+                    .unwrap_or(0);
+
+                picked.push(words[word_idx as usize]);
+
+                bigint /= &wordlist_length;
+            }
+
+            Ok(picked.join(separator))
+        },
     }
 }
 
-pub fn hash(hashing_algorithm: &HashingAlgorithm, data: &[u8]) -> Vec<u8> {
+/// Hashes `data` with a fixed-output algorithm. Don't panic on `Argon2id`;
+/// use `argon2id()` (or `encode()`) for that instead.
+pub fn hash(hashing_algorithm: &HashingAlgorithm, data: &[u8]) -> Result<Vec<u8>> {
     match hashing_algorithm {
         HashingAlgorithm::Sha256 => {
             let mut hasher = Sha256::new();
             hasher.update(data);
-            hasher.finalize().to_vec()
+            Ok(hasher.finalize().to_vec())
         },
         HashingAlgorithm::Sha512 => {
             let mut hasher = Sha512::new();
             hasher.update(data);
-            hasher.finalize().to_vec()
-        }
+            Ok(hasher.finalize().to_vec())
+        },
+        HashingAlgorithm::Argon2id { .. } => Err(HashingError::Argon2NotSupportedByHash),
     }
 }
 
+/// Derives `hash_len` raw bytes from `password`/`salt` using Argon2id.
+/// Unlike [`hash`], needs the password and salt as separate inputs.
+pub fn argon2id(
+    password: &[u8],
+    salt: &[u8],
+    mem_kib: u32,
+    time_cost: u32,
+    parallelism: u32,
+    hash_len: usize,
+) -> Result<Vec<u8>> {
+    if mem_kib > MAX_ARGON2_MEM_KIB {
+        return Err(HashingError::Argon2MemoryTooLarge(mem_kib));
+    }
+
+    let params = Params::new(mem_kib, time_cost, parallelism, Some(hash_len))
+        .map_err(HashingError::InvalidArgon2Parameters)?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+    let mut salt_hash = Sha256::new();
+    salt_hash.update(salt);
+    let salt = salt_hash.finalize();
+
+    let mut out = vec![0u8; hash_len];
+    argon2
+        .hash_password_into(password, &salt, &mut out)
+        .map_err(HashingError::Argon2Hashing)?;
+
+    Ok(out)
+}
+
 pub fn salt(salting_algorithm: &SaltingAlgorithm, data: &[u8], salt: &[u8]) -> Vec<u8> {
     match salting_algorithm {
         SaltingAlgorithm::Append => {
@@ -179,28 +305,402 @@ pub fn salt(salting_algorithm: &SaltingAlgorithm, data: &[u8], salt: &[u8]) -> V
     }
 }
 
-pub fn encode(passphrase: &[u8], code: &[u8], settings: &AlgorithmSettings) -> Result<String> {
-    let mut salted = salt(&settings.salting, code, passphrase);
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Upper,
+    Lower,
+    Digit,
+    Special,
+}
 
-    for _ in 1..settings.salting_iterations {
-        salted = salt(&settings.salting, &salted, passphrase);
+fn classify(c: char) -> CharClass {
+    if c.is_ascii_uppercase() {
+        CharClass::Upper
+    } else if c.is_ascii_lowercase() {
+        CharClass::Lower
+    } else if c.is_ascii_digit() {
+        CharClass::Digit
+    } else {
+        CharClass::Special
     }
+}
 
-    let mut hashed = hash(&settings.hashing, &salted);
+/// The set of characters `digest_algorithm` can actually produce, so
+/// `enforce_policy()` never substitutes outside the selected format.
+fn digest_domain(digest_algorithm: &DigestAlgorithm) -> Vec<char> {
+    match digest_algorithm {
+        DigestAlgorithm::Hex => "0123456789abcdef".chars().collect(),
+        DigestAlgorithm::Base64 => {
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=".chars().collect()
+        },
+        DigestAlgorithm::Base64Url => {
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_".chars().collect()
+        },
+        DigestAlgorithm::CustomAlphabet(alphabet) => alphabet.chars().collect(),
+        DigestAlgorithm::Wordlist { .. } => Vec::new(),
+    }
+}
 
-    for _ in 1..settings.hashing_iterations {
-        hashed = hash(&settings.hashing, &hashed);
+/// Deterministic byte stream for `enforce_policy()`'s substitution choices,
+/// derived from `hashed` so it never reuses bytes `digest()` consumed.
+struct PolicyStream {
+    hashed: Vec<u8>,
+    counter: u64,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl PolicyStream {
+    fn new(hashed: &[u8]) -> Self {
+        let mut stream = Self {
+            hashed: hashed.to_vec(),
+            counter: 0,
+            buf: Vec::new(),
+            pos: 0,
+        };
+        stream.refill();
+        stream
     }
 
-    let digested = digest(&settings.digest, &hashed)?;
+    fn refill(&mut self) {
+        let mut hasher = Sha512::new();
+        hasher.update(&self.hashed);
+        hasher.update(self.counter.to_le_bytes());
+        self.buf = hasher.finalize().to_vec();
+        self.counter += 1;
+        self.pos = 0;
+    }
+
+    fn next_byte(&mut self) -> u8 {
+        if self.pos >= self.buf.len() {
+            self.refill();
+        }
+        let b = self.buf[self.pos];
+        self.pos += 1;
+        b
+    }
+}
+
+/// Forces `digested` to satisfy `policy`'s minimum per-class counts by
+/// substituting characters drawn from `digest_algorithm`'s own domain (see
+/// `digest_domain()`), so the output never leaves the format the user picked.
+fn enforce_policy(
+    digested: &str,
+    hashed: &[u8],
+    policy: &CharacterClassPolicy,
+    digest_algorithm: &DigestAlgorithm,
+) -> Result<String> {
+    let mut chars: Vec<char> = digested.chars().collect();
+
+    if policy.total() > chars.len() {
+        return Err(HashingError::PolicyExceedsMaxLength(policy.total(), chars.len()));
+    }
+
+    let mut uppercase = 0usize;
+    let mut lowercase = 0usize;
+    let mut digit = 0usize;
+    let mut special = 0usize;
+
+    for &c in &chars {
+        match classify(c) {
+            CharClass::Upper => uppercase += 1,
+            CharClass::Lower => lowercase += 1,
+            CharClass::Digit => digit += 1,
+            CharClass::Special => special += 1,
+        }
+    }
+
+    let mut counts = [uppercase, lowercase, digit, special];
+    let mins = [policy.min_uppercase, policy.min_lowercase, policy.min_digit, policy.min_special];
+    let classes = [
+        (CharClass::Upper, "uppercase"),
+        (CharClass::Lower, "lowercase"),
+        (CharClass::Digit, "digit"),
+        (CharClass::Special, "special"),
+    ];
+
+    let domain = digest_domain(digest_algorithm);
+    let mut stream = PolicyStream::new(hashed);
+    let mut claimed = vec![false; chars.len()];
+
+    for (class, label) in classes {
+        let k = class as usize;
+        if counts[k] >= mins[k] {
+            continue;
+        }
 
-    if let Some(n) = settings.max_length {
-        if digested.len() > n {
-            Ok(digested[0..n].to_owned())
-        } else {
-            Ok(digested)
+        let pool: Vec<char> = domain.iter().copied().filter(|&c| classify(c) == class).collect();
+
+        if pool.is_empty() {
+            return Err(HashingError::PolicyUnsatisfiableForDigest(label, digest_algorithm.clone()));
         }
+
+        while counts[k] < mins[k] {
+            let idx = stream.next_byte() as usize % chars.len();
+            let old = classify(chars[idx]) as usize;
+
+            // Only replace a character whose class has more than its minimum.
+            if claimed[idx] || old == k || counts[old] <= mins[old] {
+                continue;
+            }
+
+            claimed[idx] = true;
+            chars[idx] = pool[stream.next_byte() as usize % pool.len()];
+            counts[old] -= 1;
+            counts[k] += 1;
+        }
+    }
+
+    Ok(chars.into_iter().collect())
+}
+
+/// Runs the salting/hashing (or Argon2id) stage shared by `encode()` and
+/// `derive_keypair()`, stopping short of `digest()` so both can build their
+/// own output representation on top of the same raw hash bytes.
+fn derive_hash(passphrase: &[u8], code: &[u8], settings: &AlgorithmSettings) -> Result<Vec<u8>> {
+    if let HashingAlgorithm::Argon2id { mem_kib, time_cost, parallelism, hash_len } = settings.hashing {
+        argon2id(code, passphrase, mem_kib, time_cost, parallelism, hash_len)
     } else {
-        Ok(digested)
+        let mut salted = salt(&settings.salting, code, passphrase);
+
+        for _ in 1..settings.salting_iterations {
+            salted = salt(&settings.salting, &salted, passphrase);
+        }
+
+        let mut hashed = hash(&settings.hashing, &salted)?;
+
+        for _ in 1..settings.hashing_iterations {
+            hashed = hash(&settings.hashing, &hashed)?;
+        }
+
+        Ok(hashed)
+    }
+}
+
+pub fn encode(passphrase: &[u8], code: &[u8], settings: &AlgorithmSettings) -> Result<String> {
+    let hashed = derive_hash(passphrase, code, settings)?;
+
+    let digested = digest(&settings.digest, &hashed)?;
+
+    let digested = match settings.max_length {
+        Some(_) if matches!(settings.digest, DigestAlgorithm::Wordlist { .. }) => {
+            return Err(HashingError::MaxLengthIncompatibleWithWordlist);
+        },
+        Some(n) if digested.len() > n => digested[0..n].to_owned(),
+        _ => digested,
+    };
+
+    match &settings.policy {
+        Some(_) if matches!(settings.digest, DigestAlgorithm::Wordlist { .. }) => {
+            Err(HashingError::PolicyIncompatibleWithWordlist)
+        },
+        Some(policy) => enforce_policy(&digested, &hashed, policy, &settings.digest),
+        None => Ok(digested),
+    }
+}
+
+fn encode_ssh_string(buf: &mut Vec<u8>, data: &[u8]) {
+    buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    buf.extend_from_slice(data);
+}
+
+/// An Ed25519 key pair derived from a passphrase and service, "brain wallet"
+/// style. The secret is never `Display`ed, only via `secret_key_hex()`.
+#[derive(Clone)]
+pub struct KeyPair {
+    pub seed: [u8; 32],
+    pub secret: SigningKey,
+    pub public: VerifyingKey,
+}
+
+impl KeyPair {
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.public.as_bytes())
+    }
+
+    pub fn secret_key_hex(&self) -> String {
+        hex::encode(self.secret.to_bytes())
+    }
+
+    /// Renders the public key as an OpenSSH `authorized_keys` line.
+    pub fn public_key_openssh(&self) -> String {
+        let mut wire = Vec::new();
+        encode_ssh_string(&mut wire, b"ssh-ed25519");
+        encode_ssh_string(&mut wire, self.public.as_bytes());
+
+        format!("ssh-ed25519 {}", general_purpose::STANDARD.encode(wire))
+    }
+}
+
+impl Display for KeyPair {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.public_key_openssh())
+    }
+}
+
+/// Derives a reproducible Ed25519 key pair from `passphrase` and `code`,
+/// reusing the same salting/hashing/KDF pipeline as `encode()`.
+pub fn derive_keypair(passphrase: &[u8], code: &[u8], settings: &AlgorithmSettings) -> Result<KeyPair> {
+    let hashed = derive_hash(passphrase, code, settings)?;
+
+    let mut seed_hasher = Sha256::new();
+    seed_hasher.update(&hashed);
+    let seed: [u8; 32] = seed_hasher.finalize().into();
+
+    let secret = SigningKey::from_bytes(&seed);
+    let public = secret.verifying_key();
+
+    Ok(KeyPair { seed, secret, public })
+}
+
+/// Number of word-list tokens a fingerprint is made of.
+const FINGERPRINT_TOKENS: usize = 3;
+
+/// Derives a short tag from `passphrase` alone, shown live next to the
+/// passphrase field so a typo is noticed before a wrong password is generated.
+pub fn fingerprint(passphrase: &[u8]) -> String {
+    let hashed = hash(&HashingAlgorithm::default(), passphrase)
+        .expect("HashingAlgorithm::default() is always supported by hash()");
+
+    let words = wordlist();
+    let wordlist_length: BigUint = words.len().into();
+    let mut bigint = BigUint::from_bytes_le(&hashed);
+
+    let mut tokens = Vec::with_capacity(FINGERPRINT_TOKENS);
+
+    for _ in 0..FINGERPRINT_TOKENS {
+        let idx = (&bigint % &wordlist_length)
+            .to_u64_digits().first()
+            .copied()
+            .unwrap_or(0);
+
+        tokens.push(words[idx as usize]);
+
+        bigint /= &wordlist_length;
+    }
+
+    tokens.join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argon2id_rejects_oversized_memory_cost() {
+        let res = argon2id(b"password", b"salt", MAX_ARGON2_MEM_KIB + 1, 2, 1, 32);
+        assert!(matches!(res, Err(HashingError::Argon2MemoryTooLarge(_))));
+    }
+
+    #[test]
+    fn argon2id_is_deterministic() {
+        let a = argon2id(b"password", b"salt", 8192, 1, 1, 32).unwrap();
+        let b = argon2id(b"password", b"salt", 8192, 1, 1, 32).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn hash_rejects_argon2id() {
+        let algorithm = HashingAlgorithm::Argon2id {
+            mem_kib: 8192,
+            time_cost: 1,
+            parallelism: 1,
+            hash_len: 32,
+        };
+        let res = hash(&algorithm, b"data");
+        assert!(matches!(res, Err(HashingError::Argon2NotSupportedByHash)));
+    }
+
+    #[test]
+    fn enforce_policy_meets_deficits_without_growing_output() {
+        let hashed = hash(&HashingAlgorithm::Sha256, b"test").unwrap();
+        let digested = digest(&DigestAlgorithm::Base64, &hashed).unwrap();
+        let policy = CharacterClassPolicy {
+            min_uppercase: 3,
+            min_lowercase: 3,
+            min_digit: 8,
+            min_special: 0,
+        };
+
+        let result = enforce_policy(&digested, &hashed, &policy, &DigestAlgorithm::Base64).unwrap();
+        assert_eq!(result.len(), digested.len());
+
+        let (mut upper, mut lower, mut digit) = (0usize, 0usize, 0usize);
+        for c in result.chars() {
+            match classify(c) {
+                CharClass::Upper => upper += 1,
+                CharClass::Lower => lower += 1,
+                CharClass::Digit => digit += 1,
+                CharClass::Special => {},
+            }
+        }
+        assert!(upper >= policy.min_uppercase);
+        assert!(lower >= policy.min_lowercase);
+        assert!(digit >= policy.min_digit);
+
+        let changed = result.chars().zip(digested.chars()).filter(|(a, b)| a != b).count();
+        assert!(changed <= policy.total());
+    }
+
+    #[test]
+    fn enforce_policy_rejects_class_unsatisfiable_by_digest() {
+        let hashed = hash(&HashingAlgorithm::Sha256, b"test").unwrap();
+        let digested = digest(&DigestAlgorithm::Hex, &hashed).unwrap();
+        let policy = CharacterClassPolicy {
+            min_special: 1,
+            ..Default::default()
+        };
+
+        let res = enforce_policy(&digested, &hashed, &policy, &DigestAlgorithm::Hex);
+        assert!(matches!(res, Err(HashingError::PolicyUnsatisfiableForDigest(_, _))));
+    }
+
+    #[test]
+    fn wordlist_digest_is_stable_for_the_same_input() {
+        let hashed = hash(&HashingAlgorithm::Sha256, b"test").unwrap();
+        let algorithm = DigestAlgorithm::Wordlist { word_count: 4, separator: "-".into() };
+
+        let a = digest(&algorithm, &hashed).unwrap();
+        let b = digest(&algorithm, &hashed).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.split('-').count(), 4);
+    }
+
+    #[test]
+    fn wordlist_digest_rejects_word_count_beyond_available_entropy() {
+        let hashed = hash(&HashingAlgorithm::Sha256, b"test").unwrap();
+        let algorithm = DigestAlgorithm::Wordlist { word_count: 1_000_000, separator: "-".into() };
+
+        let res = digest(&algorithm, &hashed);
+        assert!(matches!(res, Err(HashingError::InsufficientWordlistEntropy(1_000_000))));
+    }
+
+    #[test]
+    fn encode_is_deterministic_for_the_same_inputs() {
+        let settings = AlgorithmSettings::default();
+        let a = encode(b"passphrase", b"service", &settings).unwrap();
+        let b = encode(b"passphrase", b"service", &settings).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn derive_keypair_is_deterministic_for_the_same_inputs() {
+        let settings = AlgorithmSettings::default();
+        let a = derive_keypair(b"passphrase", b"service", &settings).unwrap();
+        let b = derive_keypair(b"passphrase", b"service", &settings).unwrap();
+        assert_eq!(a.seed, b.seed);
+        assert_eq!(a.secret_key_hex(), b.secret_key_hex());
+        assert_eq!(a.public_key_hex(), b.public_key_hex());
+    }
+
+    #[test]
+    fn fingerprint_is_stable_and_sensitive_to_typos() {
+        let a = fingerprint(b"correct horse battery staple");
+        let b = fingerprint(b"correct horse battery staple");
+        let c = fingerprint(b"correct horse battery stapme");
+
+        assert_eq!(a, b);
+        assert_eq!(a.split('-').count(), FINGERPRINT_TOKENS);
+        assert_ne!(a, c);
     }
 }